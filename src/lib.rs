@@ -1,21 +1,100 @@
 use std::collections::HashSet;
+use std::ops::{Add, Div, Mul, Sub};
 
+use num_rational::BigRational;
+use num_traits::{One, Signed, Zero};
+
+/// Scalar backend for the simplex solver.
+///
+/// The solver never compares values against a global `EPS` directly; instead it
+/// asks the scalar type whether a quantity `is_zero` or `is_positive`. This lets
+/// `f64` keep a tolerance-based notion of equality while an exact `Rational`
+/// decides the same predicates by true equality, so the ratio test and the
+/// "all coefficients <= 0 => unbounded" check stay reliable on ill-conditioned
+/// inputs.
+pub trait Scalar:
+    Clone
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+{
+    /// additive identity
+    fn zero() -> Self;
+    /// multiplicative identity
+    fn one() -> Self;
+
+    /// whether the value is (within tolerance, for inexact backends) zero
+    fn is_zero(&self) -> bool;
+    /// whether the value is strictly positive
+    fn is_positive(&self) -> bool;
+
+    /// whether the value is strictly negative
+    fn is_negative(&self) -> bool {
+        !self.is_zero() && !self.is_positive()
+    }
+
+    /// whether `self` is strictly greater than `other`
+    fn gt(&self, other: &Self) -> bool {
+        (self.clone() - other.clone()).is_positive()
+    }
+}
+
+/// `f64` backend comparing against a fixed tolerance.
 const EPS: f64 = 1e-8;
 
+impl Scalar for f64 {
+    fn zero() -> Self {
+        0.0
+    }
+
+    fn one() -> Self {
+        1.0
+    }
+
+    fn is_zero(&self) -> bool {
+        self.abs() <= EPS
+    }
+
+    fn is_positive(&self) -> bool {
+        *self > EPS
+    }
+}
+
+/// Exact rational backend backed by `num-bigint`, deciding the feasibility and
+/// optimality predicates by true equality so pivots never accumulate round-off.
+impl Scalar for BigRational {
+    fn zero() -> Self {
+        Zero::zero()
+    }
+
+    fn one() -> Self {
+        One::one()
+    }
+
+    fn is_zero(&self) -> bool {
+        Zero::is_zero(self)
+    }
+
+    fn is_positive(&self) -> bool {
+        Signed::is_positive(self)
+    }
+}
+
 /// Standard form for LP
 ///
 /// maximize   sum_{j} c[j] * x[j]
 /// subject to sum_{j} a[i][j] * x[j] <= b[j] (for all i)
 ///            x[j] >= 0 (for all j)
 #[derive(Debug, Clone)]
-pub struct StandardForm {
-    c: Vec<f64>,
-    a: Vec<Vec<f64>>,
-    b: Vec<f64>,
+pub struct StandardForm<S: Scalar> {
+    c: Vec<S>,
+    a: Vec<Vec<S>>,
+    b: Vec<S>,
 }
 
-impl StandardForm {
-    pub fn new(c: Vec<f64>, a: Vec<Vec<f64>>, b: Vec<f64>) -> Self {
+impl<S: Scalar> StandardForm<S> {
+    pub fn new(c: Vec<S>, a: Vec<Vec<S>>, b: Vec<S>) -> Self {
         let dimensions = c.len();
         let num_constraints = b.len();
 
@@ -31,7 +110,7 @@ impl StandardForm {
         StandardForm { c, a, b }
     }
 
-    fn into_slack_from(&self) -> SlackForm {
+    fn into_slack_from(&self) -> SlackForm<S> {
         let num_nonbasic = self.c.len();
         let num_basic = self.b.len();
         let num_variables = num_nonbasic + num_basic;
@@ -46,21 +125,21 @@ impl StandardForm {
             basic.insert(i);
         }
 
-        let mut a = vec![vec![0.0; num_variables]; num_variables];
+        let mut a = vec![vec![S::zero(); num_variables]; num_variables];
         for &i in basic.iter() {
             for &j in nonbasic.iter() {
-                a[i][j] = self.a[i - num_basic][j];
+                a[i][j] = self.a[i - num_nonbasic][j].clone();
             }
         }
 
-        let mut b = vec![0.0; num_variables];
+        let mut b = vec![S::zero(); num_variables];
         for &i in basic.iter() {
-            b[i] = self.b[i - num_basic];
+            b[i] = self.b[i - num_nonbasic].clone();
         }
 
-        let mut c = vec![0.0; num_variables];
+        let mut c = vec![S::zero(); num_variables];
         for &j in nonbasic.iter() {
-            c[j] = self.c[j];
+            c[j] = self.c[j].clone();
         }
 
         SlackForm {
@@ -69,13 +148,13 @@ impl StandardForm {
             a,
             b,
             c,
-            v: 0.0,
+            v: S::zero(),
         }
     }
 
     /// return slack form of auxiliary problem of the original standard form
     /// variables are ordered by [nonbasic, basic, auxiliary_variable]
-    fn into_auxiliary_slack_form(&self) -> SlackForm {
+    fn into_auxiliary_slack_form(&self) -> SlackForm<S> {
         let num_nonbasic = self.c.len();
         let num_basic = self.b.len();
         let num_variables = num_nonbasic + num_basic;
@@ -90,25 +169,25 @@ impl StandardForm {
             basic.insert(i);
         }
 
-        let mut a = vec![vec![0.0; num_variables + 1]; num_variables + 1];
+        let mut a = vec![vec![S::zero(); num_variables + 1]; num_variables + 1];
         for &i in basic.iter() {
             for &j in nonbasic.iter() {
-                a[i][j] = self.a[i - num_nonbasic][j];
+                a[i][j] = self.a[i - num_nonbasic][j].clone();
             }
         }
 
-        let mut b = vec![0.0; num_variables + 1];
+        let mut b = vec![S::zero(); num_variables + 1];
         for &i in basic.iter() {
-            b[i] = self.b[i - num_nonbasic];
+            b[i] = self.b[i - num_nonbasic].clone();
         }
 
         let vaux = num_variables; // new variable for auxiliary problem
         nonbasic.insert(vaux);
         for &i in basic.iter() {
-            a[i][vaux] = -1.0;
+            a[i][vaux] = S::zero() - S::one();
         }
-        let mut c = vec![0.0; num_variables + 1];
-        c[vaux] = -1.0;
+        let mut c = vec![S::zero(); num_variables + 1];
+        c[vaux] = S::zero() - S::one();
 
         SlackForm {
             nonbasic,
@@ -116,7 +195,7 @@ impl StandardForm {
             a,
             b,
             c,
-            v: 0.0,
+            v: S::zero(),
         }
     }
 }
@@ -126,53 +205,53 @@ impl StandardForm {
 /// z = v + sum_{j in nonbasic} c[j] * x[j]
 /// x[i] = b[i] - sum_{j in nonbasic} a[i][j] * x[j] (for i in basic)
 #[derive(Debug, Clone)]
-pub struct SlackForm {
+pub struct SlackForm<S: Scalar> {
     nonbasic: HashSet<usize>, // N
     basic: HashSet<usize>,    // B
-    a: Vec<Vec<f64>>,
-    b: Vec<f64>,
-    c: Vec<f64>,
-    v: f64,
+    a: Vec<Vec<S>>,
+    b: Vec<S>,
+    c: Vec<S>,
+    v: S,
 }
 
-impl SlackForm {
+impl<S: Scalar> SlackForm<S> {
     /// pivot basic variable `leaving` and nonbasic variable `entering`
     fn pivot(&self, leaving: usize, entering: usize) -> Self {
         let num_nonbasic = self.nonbasic.len();
         let num_basic = self.basic.len();
         let num_variables = num_nonbasic + num_basic;
 
-        let mut nonbasic= self.nonbasic.clone();
+        let mut nonbasic = self.nonbasic.clone();
         nonbasic.remove(&entering);
 
         let mut basic = self.basic.clone();
         basic.remove(&leaving);
 
-        let mut b = vec![0.0; num_variables];
-        b[entering] = self.b[leaving] / self.a[leaving][entering];
+        let mut b = vec![S::zero(); num_variables];
+        b[entering] = self.b[leaving].clone() / self.a[leaving][entering].clone();
         for &i in self.basic.iter() {
-            b[i] = self.b[i] - self.a[i][entering] * b[entering];
+            b[i] = self.b[i].clone() - self.a[i][entering].clone() * b[entering].clone();
         }
 
-        let mut a = vec![vec![0.0; num_variables]; num_variables];
-        a[entering][leaving] = 1.0 / self.a[leaving][entering];
+        let mut a = vec![vec![S::zero(); num_variables]; num_variables];
+        a[entering][leaving] = S::one() / self.a[leaving][entering].clone();
         for &j in self.nonbasic.iter() {
-            a[entering][j] = self.a[leaving][j] / self.a[leaving][entering];
+            a[entering][j] = self.a[leaving][j].clone() / self.a[leaving][entering].clone();
         }
         for &i in self.basic.iter() {
             for &j in self.nonbasic.iter() {
-                a[i][j] = self.a[i][j] - self.a[i][entering] * a[entering][j];
+                a[i][j] = self.a[i][j].clone() - self.a[i][entering].clone() * a[entering][j].clone();
             }
-            a[i][leaving] = -self.a[i][entering] * a[entering][leaving]
+            a[i][leaving] = (S::zero() - self.a[i][entering].clone()) * a[entering][leaving].clone();
         }
 
-        let mut c = vec![0.0; num_variables];
-        c[leaving] = -self.c[entering] * a[entering][leaving];
+        let mut c = vec![S::zero(); num_variables];
+        c[leaving] = (S::zero() - self.c[entering].clone()) * a[entering][leaving].clone();
         for &j in self.nonbasic.iter() {
-            c[j] = self.c[j] - self.c[entering] * a[entering][j];
+            c[j] = self.c[j].clone() - self.c[entering].clone() * a[entering][j].clone();
         }
 
-        let v = self.v + self.c[entering] * b[entering];
+        let v = self.v.clone() + self.c[entering].clone() * b[entering].clone();
 
         nonbasic.insert(leaving);
         basic.insert(entering);
@@ -187,41 +266,41 @@ impl SlackForm {
         }
     }
 
-    fn get_basic_solution(&self) -> Vec<f64> {
-        let mut solution = vec![0.0; self.a.len()];
+    fn get_basic_solution(&self) -> Vec<S> {
+        let mut solution = vec![S::zero(); self.a.len()];
         for &i in self.basic.iter() {
-            solution[i] = self.b[i];
+            solution[i] = self.b[i].clone();
         }
         solution
     }
 
-    fn get_objective(&self, solution: &Vec<f64>) -> f64 {
+    fn get_objective(&self, solution: &[S]) -> S {
         assert!(solution.len() == self.c.len());
-        let mut obj = self.v;
+        let mut obj = self.v.clone();
         for &j in self.nonbasic.iter() {
-            obj += self.c[j] * solution[j];
+            obj = obj + self.c[j].clone() * solution[j].clone();
         }
         obj
     }
 }
 
 #[derive(Debug)]
-pub enum LPResult {
-    Feasible((Vec<f64>, f64)),
+pub enum LPResult<S: Scalar> {
+    Feasible((Vec<S>, S)),
     Infeasible,
     Unbounded,
 }
 
-fn initialize_simplex(standard: &StandardForm) -> Option<SlackForm> {
+fn initialize_simplex<S: Scalar>(standard: &StandardForm<S>) -> Option<SlackForm<S>> {
     let mut k = 0;
-    let mut bmin = standard.b[k];
+    let mut bmin = standard.b[k].clone();
     for i in 0..standard.b.len() {
-        if bmin - standard.b[i] > EPS {
+        if bmin.gt(&standard.b[i]) {
             k = i;
-            bmin = standard.b[i];
+            bmin = standard.b[i].clone();
         }
     }
-    if bmin >= -EPS {
+    if !bmin.is_negative() {
         return Some(standard.into_slack_from());
     }
 
@@ -235,13 +314,17 @@ fn initialize_simplex(standard: &StandardForm) -> Option<SlackForm> {
     // pivot `k` and auxiliary variable
     slack_aux = slack_aux.pivot(num_nonbasic + k, vaux);
 
-    while let Some(entering) = slack_aux.c.iter().position(|&ci| ci > EPS) {
+    while let Some(entering) = slack_aux.c.iter().position(|ci| ci.is_positive()) {
         let mut leaving = 0;
-        let mut delta = f64::INFINITY;
-        for &ii in slack_aux.basic.iter().filter(|&i| slack_aux.a[*i][entering] > EPS) {
-            let dii = slack_aux.b[ii] / slack_aux.a[ii][entering];
-            if delta - dii > EPS {
-                delta = dii;
+        let mut delta: Option<S> = None;
+        for &ii in slack_aux
+            .basic
+            .iter()
+            .filter(|&i| slack_aux.a[*i][entering].is_positive())
+        {
+            let dii = slack_aux.b[ii].clone() / slack_aux.a[ii][entering].clone();
+            if delta.as_ref().map_or(true, |d| d.gt(&dii)) {
+                delta = Some(dii);
                 leaving = ii;
             }
         }
@@ -249,9 +332,13 @@ fn initialize_simplex(standard: &StandardForm) -> Option<SlackForm> {
     }
 
     let basic_solution = slack_aux.get_basic_solution();
-    if basic_solution[num_variables].abs() <= EPS {
+    if basic_solution[num_variables].is_zero() {
         if slack_aux.basic.contains(&vaux) {
-            let entering = slack_aux.nonbasic.iter().find(|&i| slack_aux.a[num_variables][*i].abs() > EPS).unwrap();
+            let entering = slack_aux
+                .nonbasic
+                .iter()
+                .find(|&i| !slack_aux.a[num_variables][*i].is_zero())
+                .unwrap();
             slack_aux = slack_aux.pivot(vaux, *entering);
         }
 
@@ -259,35 +346,35 @@ fn initialize_simplex(standard: &StandardForm) -> Option<SlackForm> {
         nonbasic.remove(&vaux);
         let basic = slack_aux.basic.clone();
 
-        let mut a = vec![vec![0.0; num_variables]; num_variables];
+        let mut a = vec![vec![S::zero(); num_variables]; num_variables];
         for i in 0..num_variables {
             for j in 0..num_variables {
-                a[i][j] = slack_aux.a[i][j];
+                a[i][j] = slack_aux.a[i][j].clone();
             }
         }
-        let mut b = vec![0.0; num_variables];
+        let mut b = vec![S::zero(); num_variables];
         for i in 0..num_variables {
-            b[i] = slack_aux.b[i];
+            b[i] = slack_aux.b[i].clone();
         }
 
-        let mut v = 0.0;
+        let mut v = S::zero();
         for &i in slack_aux.basic.iter() {
             if i < num_nonbasic {
-                v += standard.c[i] * slack_aux.b[i];
+                v = v + standard.c[i].clone() * slack_aux.b[i].clone();
             }
         }
-        let mut c = vec![0.0; num_variables];
+        let mut c = vec![S::zero(); num_variables];
         for &j in nonbasic.iter() {
             if j < num_nonbasic {
-                c[j] += standard.c[j];
+                c[j] = c[j].clone() + standard.c[j].clone();
             }
-            let mut cj = 0.0;
+            let mut cj = S::zero();
             for &i in slack_aux.basic.iter() {
                 if i < num_nonbasic {
-                    cj += standard.c[i] * slack_aux.a[i][j];
+                    cj = cj + standard.c[i].clone() * slack_aux.a[i][j].clone();
                 }
             }
-            c[j] -= cj;
+            c[j] = c[j].clone() - cj;
         }
 
         let slack = SlackForm {
@@ -298,32 +385,41 @@ fn initialize_simplex(standard: &StandardForm) -> Option<SlackForm> {
             c,
             v,
         };
-        return Some(slack);
+        Some(slack)
     } else {
-        return None;
+        None
     }
 }
 
-pub fn simplex(standard: &StandardForm) -> LPResult {
-    let eps = 1e-8;
-
+pub fn simplex<S: Scalar>(standard: &StandardForm<S>) -> LPResult<S> {
     // initialize basic solution
-    let mut slack = match initialize_simplex(&standard) {
+    let mut slack = match initialize_simplex(standard) {
         Some(slack) => slack,
         None => return LPResult::Infeasible,
     };
 
     // pivoting
-    while let Some(entering) = slack.c.iter().position(|&ci| ci > eps) {
-        if slack.basic.iter().all(|&i| slack.a[i][entering] <= eps) { // Blant's rule
+    while let Some(entering) = slack.c.iter().position(|ci| ci.is_positive()) {
+        if slack.basic.iter().all(|&i| !slack.a[i][entering].is_positive()) {
+            // Blant's rule
             return LPResult::Unbounded;
         }
         let mut leaving = 0;
-        let mut delta = f64::INFINITY;
-        for &ii in slack.basic.iter().filter(|&i| slack.a[*i][entering] > eps) { // Blant's rule
-            let dii = slack.b[ii] / slack.a[ii][entering];
-            if dii + eps < delta {
-                delta = dii;
+        let mut delta: Option<S> = None;
+        for &ii in slack
+            .basic
+            .iter()
+            .filter(|&i| slack.a[*i][entering].is_positive())
+        {
+            // Blant's rule
+            let dii = slack.b[ii].clone() / slack.a[ii][entering].clone();
+            let better = match delta.as_ref() {
+                None => true,
+                // on a tie, the smallest index leaves
+                Some(d) => d.gt(&dii) || (!dii.gt(d) && ii < leaving),
+            };
+            if better {
+                delta = Some(dii);
                 leaving = ii;
             }
         }
@@ -335,9 +431,9 @@ pub fn simplex(standard: &StandardForm) -> LPResult {
 
     // convert to solution for standard form
     let num_nonbasic = slack.nonbasic.len();
-    let mut solution = vec![0.0; num_nonbasic];
+    let mut solution = vec![S::zero(); num_nonbasic];
     for j in 0..num_nonbasic {
-        solution[j] = basic_solution[j];
+        solution[j] = basic_solution[j].clone();
     }
 
     LPResult::Feasible((solution, optimal))
@@ -347,7 +443,13 @@ pub fn simplex(standard: &StandardForm) -> LPResult {
 mod tests {
     use super::*;
     extern crate approx;
-    use approx::{assert_relative_eq};
+    use approx::assert_relative_eq;
+    use num_traits::FromPrimitive;
+
+    /// convert an `f64` literal into an exact rational for the rational backend tests
+    fn r(x: f64) -> BigRational {
+        BigRational::from_f64(x).unwrap()
+    }
 
     #[test]
     fn test_feasible() {
@@ -415,6 +517,27 @@ mod tests {
         };
     }
 
+    #[test]
+    fn test_feasible_rational() {
+        // the same program as the first `test_feasible` case, solved exactly
+        let c = vec![r(3.0), r(1.0), r(2.0)];
+        let a = vec![
+            vec![r(1.0), r(1.0), r(3.0)],
+            vec![r(2.0), r(2.0), r(5.0)],
+            vec![r(4.0), r(1.0), r(2.0)],
+        ];
+        let b = vec![r(30.0), r(24.0), r(36.0)];
+        let standard = StandardForm::new(c, a, b);
+
+        match simplex(&standard) {
+            LPResult::Feasible((solution, optimal)) => {
+                assert_eq!(solution, vec![r(8.0), r(4.0), r(0.0)]);
+                assert_eq!(optimal, r(28.0));
+            },
+            _ => unreachable!(),
+        };
+    }
+
     #[test]
     fn test_unbounded() {
         let c = vec![1.0, 3.0, -1.0];